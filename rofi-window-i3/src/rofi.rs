@@ -67,17 +67,128 @@ pub enum ModeMode {
     ResetDialog = c::ModeMode_RESET_DIALOG as u32,
 }
 
-#[macro_export]
-macro_rules! rofi_name_key {
-    (
-        $single:expr $(,)?
-    ) => {
-        unsafe {
-            &*std::mem::transmute::<_, &[c_char; 128]>(const_concat_bytes!(
-                $single,
-                &[0u8; 128 - $single.len()]
-            ))
+/// Outcome of [`RofiMode::result`].
+///
+/// Besides the usual [`ModeMode`] dialog transitions, a mode can ask the FFI
+/// glue to rewrite the search box text, e.g. to implement completion.
+#[derive(Debug)]
+pub enum ModeResult {
+    /// Let Rofi decide the next dialog state from the raw `mretv` flags.
+    Default,
+    /// Force a specific dialog transition.
+    Mode(ModeMode),
+    /// Replace the current input text with `String`.
+    ReplaceInput(String),
+    /// Append `String` to the current input text.
+    AppendInput(String),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum AttrKind {
+    Foreground(u16, u16, u16),
+    Background(u16, u16, u16),
+    Weight(c::PangoWeight),
+    Style(c::PangoStyle),
+    Underline(c::PangoUnderline),
+    Strikethrough(bool),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Attributes {
+    ranges: Vec<(usize, usize, AttrKind)>,
+}
+
+impl Attributes {
+    pub fn new() -> Self {
+        Attributes { ranges: Vec::new() }
+    }
+
+    pub fn foreground(mut self, start: usize, end: usize, r: u16, g: u16, b: u16) -> Self {
+        self.ranges
+            .push((start, end, AttrKind::Foreground(r, g, b)));
+        self
+    }
+
+    pub fn background(mut self, start: usize, end: usize, r: u16, g: u16, b: u16) -> Self {
+        self.ranges
+            .push((start, end, AttrKind::Background(r, g, b)));
+        self
+    }
+
+    pub fn weight(mut self, start: usize, end: usize, weight: c::PangoWeight) -> Self {
+        self.ranges.push((start, end, AttrKind::Weight(weight)));
+        self
+    }
+
+    pub fn style(mut self, start: usize, end: usize, style: c::PangoStyle) -> Self {
+        self.ranges.push((start, end, AttrKind::Style(style)));
+        self
+    }
+
+    pub fn underline(mut self, start: usize, end: usize, underline: c::PangoUnderline) -> Self {
+        self.ranges
+            .push((start, end, AttrKind::Underline(underline)));
+        self
+    }
+
+    pub fn strikethrough(mut self, start: usize, end: usize, enabled: bool) -> Self {
+        self.ranges
+            .push((start, end, AttrKind::Strikethrough(enabled)));
+        self
+    }
+
+    fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    // lowers the accumulated ranges into a GList of PangoAttributes for *attribute_list
+    unsafe fn into_glist(self) -> *mut c::GList {
+        let mut list: *mut c::GList = ptr::null_mut();
+
+        for (start, end, kind) in self.ranges {
+            let attr: *mut c::PangoAttribute = match kind {
+                AttrKind::Foreground(r, g, b) => c::pango_attr_foreground_new(r, g, b),
+                AttrKind::Background(r, g, b) => c::pango_attr_background_new(r, g, b),
+                AttrKind::Weight(weight) => c::pango_attr_weight_new(weight),
+                AttrKind::Style(style) => c::pango_attr_style_new(style),
+                AttrKind::Underline(underline) => c::pango_attr_underline_new(underline),
+                AttrKind::Strikethrough(enabled) => {
+                    c::pango_attr_strikethrough_new(enabled as c_int)
+                }
+            };
+
+            (*attr).start_index = start as c_uint;
+            (*attr).end_index = end as c_uint;
+
+            list = c::g_list_append(list, attr as *mut c_void);
         }
+
+        list
+    }
+}
+
+// zero-pads T::NAME into the 128-byte cfg_name_key Rofi expects
+const fn name_key(name: &'static CStr) -> [c_char; 128] {
+    let bytes = name.to_bytes();
+    assert!(
+        bytes.len() < 128,
+        "RofiMode::NAME must be shorter than 128 bytes"
+    );
+
+    let mut key = [0 as c_char; 128];
+    let mut i = 0;
+    while i < bytes.len() {
+        key[i] = bytes[i] as c_char;
+        i += 1;
+    }
+    key
+}
+
+#[macro_export]
+macro_rules! export_mode {
+    ($ty:ty) => {
+        #[no_mangle]
+        pub static mode: $crate::rofi::CRofiMode = $crate::rofi::rofi_c_mode::<$ty>();
     };
 }
 
@@ -106,28 +217,107 @@ pub mod helpers {
             c::helper_token_match(ftokens.as_mut_ptr(), token.as_ptr() as *const i8) != 0
         }
     }
+}
+
+// safe subset of the Rofi runtime API, passed into RofiMode::init()
+#[derive(Copy, Clone)]
+pub struct Api {
+    _private: (),
+}
+
+impl Api {
+    fn new() -> Self {
+        Api { _private: () }
+    }
 
-    pub fn rofi_view_hide() {
+    pub fn hide(&self) {
         // this is internal API, subject to break!
         unsafe {
             c::rofi_view_hide();
         }
     }
+
+    // note: rofi_view_get_active() is null until a view exists, which is the
+    // case while RofiMode::init() runs, so these guard against a null deref
+    pub fn set_prompt(&self, prompt: &str) {
+        unsafe {
+            let view = c::rofi_view_get_active();
+            if view.is_null() {
+                return;
+            }
+            let c_prompt = CString::new(prompt).unwrap();
+            c::rofi_view_set_prompt(view, c_prompt.as_ptr());
+        }
+    }
+
+    pub fn set_input(&self, text: &str) {
+        unsafe {
+            let view = c::rofi_view_get_active();
+            if view.is_null() {
+                return;
+            }
+            let c_text = CString::new(text).unwrap();
+            c::rofi_view_set_input(view, c_text.as_ptr());
+        }
+    }
+
+    pub fn input(&self) -> Option<String> {
+        unsafe {
+            let view = c::rofi_view_get_active();
+            if view.is_null() {
+                return None;
+            }
+            let ptr = c::rofi_view_get_input(view);
+            if ptr.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(ptr).to_string_lossy().into_owned())
+            }
+        }
+    }
+
+    pub fn reload(&self) {
+        unsafe {
+            c::rofi_view_reload();
+        }
+    }
+
+    pub fn config_string(&self, key: &str) -> Option<String> {
+        unsafe {
+            let c_key = CString::new(key).unwrap();
+            let ptr = c::config_get_string(c_key.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(ptr).to_string_lossy().into_owned())
+            }
+        }
+    }
 }
 
 pub trait RofiMode: Sized {
     const NAME: &'static CStr;
     const DISPLAY_NAME: &'static CStr;
-    const NAME_KEY: &'static [c_char; 128];
     const TYPE: ModeType;
 
-    fn init() -> Result<Self, ()>;
+    fn init(api: Api) -> Result<Self, ()>;
     fn get_num_entries(&self) -> usize;
-    // TODO: pango attributes
-    fn get_display_value(&self, selected_line: usize) -> Option<(String, EntryStateFlags)>;
-    fn result(&self, mretv: MenuReturn, selected_line: usize) -> Option<ModeMode>;
+    fn get_display_value(
+        &self,
+        selected_line: usize,
+    ) -> Option<(String, EntryStateFlags, Attributes)>;
+    fn result(&self, mretv: MenuReturn, selected_line: usize, input: &str) -> ModeResult;
     fn token_match(&self, patterns: Vec<&Pattern>, selected_line: usize) -> bool;
     fn icon_query(&self, selected_line: usize) -> Option<String>;
+
+    /// Called when the user presses the delete keybind over `selected_line`.
+    ///
+    /// Modes backed by mutable state (history lists, clipboard managers) can
+    /// remove the entry here. Return whether the list actually changed so
+    /// the FFI layer knows to request a `RELOAD_DIALOG`.
+    fn delete_entry(&mut self, _selected_line: usize) -> bool {
+        false
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Hash)]
@@ -145,8 +335,8 @@ struct ModeData<T: RofiMode> {
 }
 
 impl<T: RofiMode> ModeData<T> {
-    fn init() -> Result<Self, ()> {
-        let mode = T::init()?;
+    fn init(api: Api) -> Result<Self, ()> {
+        let mode = T::init(api)?;
         let icon_cache = Mutex::new(HashMap::new());
         Ok(ModeData { mode, icon_cache })
     }
@@ -156,12 +346,16 @@ impl c::rofi_mode {
     fn get<T: RofiMode>(&self) -> &ModeData<T> {
         unsafe { &*(self.private_data as *const ModeData<T>) }
     }
+
+    fn get_mut<T: RofiMode>(&mut self) -> &mut ModeData<T> {
+        unsafe { &mut *(self.private_data as *mut ModeData<T>) }
+    }
 }
 
 unsafe extern "C" fn _init<T: RofiMode>(mc: *mut c::rofi_mode) -> c_int {
     (*mc).display_name = T::DISPLAY_NAME.to_owned().into_raw();
 
-    let mode_data_opt = (|| -> Result<_, ()> { Ok(ModeData::<T>::init()?) })().ok();
+    let mode_data_opt = (|| -> Result<_, ()> { Ok(ModeData::<T>::init(Api::new())?) })().ok();
 
     match mode_data_opt {
         None => 0,
@@ -191,14 +385,18 @@ unsafe extern "C" fn _get_display_value<T: RofiMode>(
     mc: *const c::rofi_mode,
     selected_line: c_uint,
     state: *mut c_int,
-    _attribute_list: *mut *mut c::GList,
+    attribute_list: *mut *mut c::GList,
     get_entry: c_int,
 ) -> *mut c_char {
     let m = (*mc).get::<T>();
 
-    if let Some((dv, flags)) = m.mode.get_display_value(selected_line as usize) {
+    if let Some((dv, flags, attrs)) = m.mode.get_display_value(selected_line as usize) {
         *state = flags.bits() as i32;
 
+        if !attribute_list.is_null() && !attrs.is_empty() {
+            *attribute_list = attrs.into_glist();
+        }
+
         if get_entry == 0 {
             return ptr::null_mut();
         }
@@ -209,22 +407,69 @@ unsafe extern "C" fn _get_display_value<T: RofiMode>(
     }
 }
 
+/// Writes `text` back through a Rofi-owned `*input` out-pointer, freeing the
+/// previous string with `g_free` and handing back a fresh `g_strdup`'d one so
+/// ownership rules match what the C side expects.
+unsafe fn set_input(input: *mut *mut c_char, text: &str) {
+    if input.is_null() {
+        return;
+    }
+
+    let c_text = CString::new(text).unwrap();
+    let new_ptr = c::g_strdup(c_text.as_ptr());
+
+    if !(*input).is_null() {
+        c::g_free(*input as *mut c_void);
+    }
+
+    *input = new_ptr;
+}
+
 unsafe extern "C" fn _result<T: RofiMode>(
     mc: *mut c::rofi_mode,
     mretv: c_int,
-    _input: *mut *mut c_char,
+    input: *mut *mut c_char,
     selected_line: c_uint,
 ) -> c::ModeMode {
+    let retv = MenuReturn::from_bits(mretv as u32).unwrap();
+    let selected_line = selected_line.try_into().unwrap();
+
+    let deleted = if retv.contains(MenuReturn::EntryDelete) {
+        (*mc).get_mut::<T>().mode.delete_entry(selected_line)
+    } else {
+        false
+    };
+
     let m = (*mc).get::<T>();
 
-    // TODO: pass input
+    let input_str = if input.is_null() || (*input).is_null() {
+        String::new()
+    } else {
+        CStr::from_ptr(*input).to_string_lossy().into_owned()
+    };
 
-    match m.mode.result(
-        MenuReturn::from_bits(mretv as u32).unwrap(),
-        selected_line.try_into().unwrap(),
-    ) {
+    let mode_mode = match m.mode.result(retv, selected_line, &input_str) {
+        ModeResult::Default => None,
+        ModeResult::Mode(m) => Some(m),
+        ModeResult::ReplaceInput(new_input) => {
+            set_input(input, &new_input);
+            None
+        }
+        ModeResult::AppendInput(suffix) => {
+            set_input(input, &(input_str + &suffix));
+            None
+        }
+    };
+
+    let base = match mode_mode {
         Some(e) => e as c_uint,
         None => (mretv as u32) & c::MenuReturn_MENU_LOWER_MASK,
+    };
+
+    if deleted {
+        c::ModeMode_RELOAD_DIALOG as c_uint
+    } else {
+        base
     }
 }
 
@@ -285,7 +530,7 @@ pub const fn rofi_c_mode<T: RofiMode>() -> c::rofi_mode {
         let mut mc: c::rofi_mode = std::mem::zeroed();
         mc.abi_version = c::ABI_VERSION;
         mc.name = T::NAME.as_ptr() as *mut i8;
-        mc.cfg_name_key = *T::NAME_KEY;
+        mc.cfg_name_key = name_key(T::NAME);
 
         mc._init = Some(_init::<T>);
         mc._destroy = Some(_destroy::<T>);